@@ -0,0 +1,101 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+
+/// Abstracts the primitive reads `MIDIFile` needs over a `MThd`/`MTrk`
+/// byte stream, so parsing can run against an in-memory buffer or a
+/// streaming file reader identically. Only `get_byte` is required; the
+/// multi-byte and VLQ reads are built from it.
+pub trait ByteSource {
+    fn get_byte(&mut self) -> Result<u8, String>;
+
+    fn get_word(&mut self) -> Result<u16, String> {
+        Ok((self.get_byte()? as u16) << 8 |
+           (self.get_byte()? as u16))
+    }
+
+    fn get_dword(&mut self) -> Result<u32, String> {
+        Ok((self.get_byte()? as u32) << 24 |
+           (self.get_byte()? as u32) << 16 |
+           (self.get_byte()? as u32) << 8  |
+           (self.get_byte()? as u32))
+    }
+
+    fn get_string(&mut self, len: usize) -> Result<String, String> {
+        let mut str = String::new();
+        for _i in 0..len {
+            str.push_str(&(self.get_byte()? as char).to_string());
+        }
+        Ok(str)
+    }
+
+    fn get_vlq(&mut self) -> Result<u32, String> {
+        let mut value: u32 = 0;
+        loop {
+            let byte = self.get_byte()?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        Ok(value)
+    }
+
+    /// Discards `n` bytes, e.g. to jump over a chunk by its length dword
+    /// without decoding it.
+    fn skip(&mut self, n: u32) -> Result<(), String> {
+        for _i in 0..n {
+            self.get_byte()?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads from an in-memory buffer, as produced by slurping a whole file.
+pub struct BufferSource {
+    buffer: Vec<u8>,
+    pos: usize,
+}
+
+impl BufferSource {
+    pub fn new(buffer: Vec<u8>) -> BufferSource {
+        BufferSource { buffer, pos: 0 }
+    }
+}
+
+impl ByteSource for BufferSource {
+    fn get_byte(&mut self) -> Result<u8, String> {
+        if self.pos == self.buffer.len() {
+            return Err("File ended unexpectedly".to_string());
+        }
+        let byte = self.buffer[self.pos];
+        self.pos += 1;
+        Ok(byte)
+    }
+}
+
+/// Reads lazily from a buffered file, keeping memory bounded regardless
+/// of file size.
+pub struct FileSource {
+    reader: BufReader<File>,
+}
+
+impl FileSource {
+    pub fn new(file: File) -> FileSource {
+        FileSource { reader: BufReader::new(file) }
+    }
+}
+
+impl ByteSource for FileSource {
+    fn get_byte(&mut self) -> Result<u8, String> {
+        let mut byte = [0u8; 1];
+        match self.reader.read(&mut byte) {
+            Ok(0) => Err("File ended unexpectedly".to_string()),
+            Ok(_) => Ok(byte[0]),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    fn skip(&mut self, n: u32) -> Result<(), String> {
+        self.reader.seek_relative(n as i64).map_err(|err| err.to_string())
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{Division, Event, FileFormat, MIDIFile, MetaEvent, Track, TrackEvent};
+
+/// Ticks-per-quarter-note used for the MIDI file synthesized from a
+/// module. Rows get a whole number of ticks when `speed * rows_per_beat`
+/// divides 960 (as with the defaults, 6 * 4); otherwise the ticks per row
+/// are rounded to the nearest whole tick.
+const PPQ: u16 = 960;
+
+const DEFAULT_SPEED: u8 = 6;
+const DEFAULT_TEMPO: u8 = 125;
+const DEFAULT_ROWS_PER_BEAT: u8 = 4;
+
+const NOTE_OFF: u8 = 255;
+const NOTE_CUT: u8 = 254;
+
+/// Little-endian byte cursor over an in-memory `.it` file.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data, pos: 0 }
+    }
+
+    fn seek(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    fn get_bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.pos + len > self.data.len() {
+            return Err("File ended unexpectedly".to_string());
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn get_u8(&mut self) -> Result<u8, String> {
+        Ok(self.get_bytes(1)?[0])
+    }
+
+    fn get_u16(&mut self) -> Result<u16, String> {
+        let bytes = self.get_bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn get_u32(&mut self) -> Result<u32, String> {
+        let bytes = self.get_bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn get_string(&mut self, len: usize) -> Result<String, String> {
+        let bytes = self.get_bytes(len)?;
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(len);
+        Ok(bytes[..end].iter().map(|&b| b as char).collect())
+    }
+}
+
+struct Cell {
+    note: Option<u8>,
+    instrument: Option<u8>,
+    volume: Option<u8>,
+    command: Option<(u8, u8)>,
+}
+
+struct Pattern {
+    rows: Vec<Vec<Cell>>,
+}
+
+/// Reads an Impulse Tracker module and converts its pattern/row grid into
+/// the same in-memory `MIDIFile` structure produced by parsing an `.mid`
+/// file, so the existing viewer/writer/renderer work on it unchanged.
+pub fn import(buffer: Vec<u8>) -> Result<MIDIFile, String> {
+    let mut r = Reader::new(&buffer);
+
+    let identifier = r.get_string(4)?;
+    if identifier != "IMPM" {
+        return Err(format!("Wrong identifier for IT module: Expected \"IMPM\" but got \"{}\"", identifier));
+    }
+    let _song_name = r.get_string(26)?;
+
+    r.seek(0x1E);
+    let rows_per_beat = match r.get_u8()? {
+        0 => DEFAULT_ROWS_PER_BEAT,
+        highlight => highlight,
+    };
+    let _rows_per_measure = r.get_u8()?;
+
+    r.seek(0x20);
+    let order_count = r.get_u16()? as usize;
+    let instrument_count = r.get_u16()? as usize;
+    let sample_count = r.get_u16()? as usize;
+    let pattern_count = r.get_u16()? as usize;
+
+    r.seek(0x32);
+    let initial_speed = match r.get_u8()? {
+        0 => DEFAULT_SPEED,
+        speed => speed,
+    };
+    let initial_tempo = match r.get_u8()? {
+        0 => DEFAULT_TEMPO,
+        tempo => tempo,
+    };
+
+    r.seek(0xC0);
+    let orders = r.get_bytes(order_count)?.to_vec();
+
+    let instrument_offsets_start = 0xC0 + order_count;
+    r.seek(instrument_offsets_start + instrument_count * 4 + sample_count * 4);
+    let mut pattern_offsets = Vec::with_capacity(pattern_count);
+    for _i in 0..pattern_count {
+        pattern_offsets.push(r.get_u32()?);
+    }
+
+    let patterns = pattern_offsets.iter()
+        .map(|&offset| match offset {
+            0 => Ok(None),
+            offset => read_pattern(&buffer, offset as usize).map(Some),
+        })
+        .collect::<Result<Vec<Option<Pattern>>, String>>()?;
+
+    Ok(play(&orders, &patterns, rows_per_beat, initial_speed, initial_tempo))
+}
+
+fn read_pattern(buffer: &[u8], offset: usize) -> Result<Pattern, String> {
+    let mut r = Reader::new(buffer);
+    r.seek(offset);
+    let packed_length = r.get_u16()? as usize;
+    let num_rows = r.get_u16()? as usize;
+    let _reserved = r.get_u32()?;
+
+    let packed_end = r.pos + packed_length;
+    let mut last_mask = [0u8; 64];
+    let mut last_note = [0u8; 64];
+    let mut last_instrument = [0u8; 64];
+    let mut last_volume = [0u8; 64];
+    let mut last_command = [(0u8, 0u8); 64];
+
+    let mut rows: Vec<Vec<Cell>> = (0..num_rows).map(|_| {
+        (0..64).map(|_| Cell { note: None, instrument: None, volume: None, command: None }).collect()
+    }).collect();
+
+    for row in rows.iter_mut() {
+        loop {
+            if r.pos >= packed_end {
+                break;
+            }
+            let channel_variable = r.get_u8()?;
+            if channel_variable == 0 {
+                break;
+            }
+            let channel = ((channel_variable - 1) & 63) as usize;
+
+            let mask = if channel_variable & 0x80 != 0 {
+                let mask = r.get_u8()?;
+                last_mask[channel] = mask;
+                mask
+            } else {
+                last_mask[channel]
+            };
+
+            let cell = &mut row[channel];
+            if mask & 1 != 0 {
+                let note = r.get_u8()?;
+                last_note[channel] = note;
+                cell.note = Some(note);
+            } else if mask & 16 != 0 {
+                cell.note = Some(last_note[channel]);
+            }
+            if mask & 2 != 0 {
+                let instrument = r.get_u8()?;
+                last_instrument[channel] = instrument;
+                cell.instrument = Some(instrument);
+            } else if mask & 32 != 0 {
+                cell.instrument = Some(last_instrument[channel]);
+            }
+            if mask & 4 != 0 {
+                let volume = r.get_u8()?;
+                last_volume[channel] = volume;
+                cell.volume = Some(volume);
+            } else if mask & 64 != 0 {
+                cell.volume = Some(last_volume[channel]);
+            }
+            if mask & 8 != 0 {
+                let command = r.get_u8()?;
+                let value = r.get_u8()?;
+                last_command[channel] = (command, value);
+                cell.command = Some((command, value));
+            } else if mask & 128 != 0 {
+                cell.command = Some(last_command[channel]);
+            }
+        }
+    }
+
+    Ok(Pattern { rows })
+}
+
+/// Walks the order list and its patterns with a small player that tracks
+/// `speed`, `tempo` and `rows_per_beat`, emitting one MIDI track per
+/// module channel that plays notes (silent channels get none) plus a
+/// conductor track carrying the tempo map.
+fn play(orders: &[u8], patterns: &[Option<Pattern>], rows_per_beat: u8, initial_speed: u8, initial_tempo: u8) -> MIDIFile {
+    let mut speed = initial_speed as u32;
+    let mut tempo = initial_tempo as u32;
+    let mut absolute_tick: u64 = 0;
+
+    let mut tempo_events: Vec<TrackEvent> = vec![TrackEvent {
+        delta_time: 0,
+        event: Event::Meta(MetaEvent::SetTempo { microseconds_per_quarter_note: 60_000_000 / tempo }),
+    }];
+    let mut last_tempo_event_tick: u64 = 0;
+
+    let mut channel_events: Vec<Vec<TrackEvent>> = (0..64).map(|_| Vec::new()).collect();
+    let mut last_channel_event_tick = [0u64; 64];
+    let mut playing_note = [None; 64];
+
+    for &order in orders {
+        if order == 255 {
+            break;
+        }
+        if order == 254 {
+            continue;
+        }
+
+        let Some(Some(pattern)) = patterns.get(order as usize) else {
+            continue;
+        };
+
+        for row in &pattern.rows {
+            let ticks_per_row = (PPQ as f64 / (speed as f64 * rows_per_beat as f64)).round() as u64;
+
+            for (channel, cell) in row.iter().enumerate() {
+                if let Some((command, value)) = cell.command {
+                    match command {
+                        1 if value > 0 => speed = value as u32,
+                        20 if value >= 0x20 => {
+                            tempo = value as u32;
+                            tempo_events.push(TrackEvent {
+                                delta_time: (absolute_tick - last_tempo_event_tick) as u32,
+                                event: Event::Meta(MetaEvent::SetTempo { microseconds_per_quarter_note: 60_000_000 / tempo }),
+                            });
+                            last_tempo_event_tick = absolute_tick;
+                        },
+                        _ => {},
+                    }
+                }
+
+                if cell.note.is_none() {
+                    continue;
+                }
+                let note = cell.note.unwrap();
+                let midi_channel = (channel % 16) as u8;
+
+                if let Some(previous_note) = playing_note[channel].take() {
+                    channel_events[channel].push(TrackEvent {
+                        delta_time: (absolute_tick - last_channel_event_tick[channel]) as u32,
+                        event: Event::ChannelVoice { status: 0x80, channel: midi_channel, data1: previous_note, data2: Some(0) },
+                    });
+                    last_channel_event_tick[channel] = absolute_tick;
+                }
+
+                if note == NOTE_OFF || note == NOTE_CUT || note >= 120 {
+                    continue;
+                }
+
+                let velocity = match cell.volume {
+                    Some(volume) if volume <= 64 => (volume * 2).min(127),
+                    _ => 100,
+                };
+
+                channel_events[channel].push(TrackEvent {
+                    delta_time: (absolute_tick - last_channel_event_tick[channel]) as u32,
+                    event: Event::ChannelVoice { status: 0x90, channel: midi_channel, data1: note, data2: Some(velocity) },
+                });
+                last_channel_event_tick[channel] = absolute_tick;
+                playing_note[channel] = Some(note);
+            }
+
+            absolute_tick += ticks_per_row;
+        }
+    }
+
+    tempo_events.push(TrackEvent { delta_time: (absolute_tick - last_tempo_event_tick) as u32, event: Event::Meta(MetaEvent::EndOfTrack) });
+
+    let mut tracks = vec![Track { events: tempo_events }];
+    for (channel, mut events) in channel_events.into_iter().enumerate() {
+        if events.is_empty() {
+            continue;
+        }
+        let mut last_tick = last_channel_event_tick[channel];
+        if let Some(note) = playing_note[channel] {
+            events.push(TrackEvent {
+                delta_time: (absolute_tick - last_tick) as u32,
+                event: Event::ChannelVoice { status: 0x80, channel: (channel % 16) as u8, data1: note, data2: Some(0) },
+            });
+            last_tick = absolute_tick;
+        }
+        events.push(TrackEvent { delta_time: (absolute_tick - last_tick) as u32, event: Event::Meta(MetaEvent::EndOfTrack) });
+        tracks.push(Track { events });
+    }
+
+    MIDIFile::from_tracks(FileFormat::MultipleTrack, Division::TicksPerBeat(PPQ), tracks)
+}
@@ -3,26 +3,61 @@ use std::io::Read;
 use clap::{arg, Command};
 use log::{debug, error};
 
+mod byte_source;
+mod track;
+mod meta;
+mod timing;
+mod writer;
+mod soundfont;
+mod render;
+mod itmodule;
+
+use byte_source::{BufferSource, ByteSource, FileSource};
+
+pub use track::{Track, TrackEvent, Event};
+pub use meta::MetaEvent;
+pub use timing::Division;
+pub use writer::Settings;
 
 pub struct Options {
     infile: String,
     debug: bool,
+    render: Option<String>,
+    soundfont: Option<String>,
+    from: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+pub enum InputFormat {
+    Midi,
+    ImpulseTracker,
 }
 
 pub struct MIDIFile {
-    buffer: Vec<u8>,
-    pos: usize,
+    source: Box<dyn ByteSource>,
     file_format: FileFormat,
     number_of_track_chunks: u16,
-    division: i16,
+    division: Division,
+    tracks: Vec<Track>,
 }
 
-enum FileFormat {
+#[derive(Clone, Copy)]
+pub enum FileFormat {
     SingleTrack,
     MultipleTrack,
     MultipleSong,
 }
 
+impl FileFormat {
+    pub(crate) fn as_word(&self) -> u16 {
+        match self {
+            FileFormat::SingleTrack => 0,
+            FileFormat::MultipleTrack => 1,
+            FileFormat::MultipleSong => 2,
+        }
+    }
+}
+
 impl MIDIFile {
     
     fn read_header(&mut self) -> Result<(), String> {
@@ -70,59 +105,68 @@ impl MIDIFile {
         }
         
         // Division
-        self.division = self.get_word()? as i16;
-        if self.division > 0 {
-            debug!("Division given in ticks per beat");
-        } else if self.division < 0 {
-            debug!("Division given in SMPTE format");
-        } else {
-            return Err("Division cannot be zero".to_string());
+        let raw_division = self.get_word()? as i16;
+        self.division = Division::decode(raw_division)?;
+        match self.division {
+            Division::TicksPerBeat(ticks) => debug!("Division given in ticks per beat: {}", ticks),
+            Division::Smpte { frames_per_second, ticks_per_frame } =>
+                debug!("Division given in SMPTE format: {} fps, {} ticks/frame", frames_per_second, ticks_per_frame),
         }
         Ok(())
     }
 
-    fn new(buffer: Vec<u8>) -> Result<MIDIFile, String> {
+    fn parse(source: Box<dyn ByteSource>) -> Result<MIDIFile, String> {
         let mut file = MIDIFile {
-            buffer,
-            pos: 0,
+            source,
             file_format: FileFormat::SingleTrack,
             number_of_track_chunks: 0,
-            division: 0,
+            division: Division::TicksPerBeat(0),
+            tracks: Vec::new(),
         };
 
-        match file.read_header() {
-            Ok(()) => Ok(file), 
-            Err(str) => Err(str),
-        }
+        file.read_header()?;
+        file.read_tracks()?;
+        Ok(file)
     }
-    
-    fn get_byte(&mut self) -> Result<u8, String> {
-        if self.pos == self.buffer.len() {
-            return Err("File ended unexpectedly".to_string());
-        }
-        let byte = self.buffer[self.pos];
-        self.pos += 1;
-        Ok(byte)
+
+    /// Parses a MIDI file by streaming it through a buffered reader
+    /// instead of loading it into memory up front.
+    pub fn open(path: &str) -> Result<MIDIFile, String> {
+        let file = File::open(path).map_err(|err| err.to_string())?;
+        MIDIFile::parse(Box::new(FileSource::new(file)))
     }
-    
-    fn get_string(&mut self, len: usize) -> Result<String, String> {
-        let mut str = String::new();
-        for _i in 0..len {
-            str.push_str(&(self.get_byte()? as char).to_string());
+
+    /// Builds a `MIDIFile` directly from already-decoded tracks, for
+    /// formats other than `.mid` that get converted into the same
+    /// in-memory representation (see `itmodule`).
+    pub(crate) fn from_tracks(file_format: FileFormat, division: Division, tracks: Vec<Track>) -> MIDIFile {
+        MIDIFile {
+            source: Box::new(BufferSource::new(Vec::new())),
+            file_format,
+            number_of_track_chunks: tracks.len() as u16,
+            division,
+            tracks,
         }
-        Ok(str)
     }
 
-    fn get_word(&mut self) -> Result<u16, String> {
-        Ok((self.get_byte()? as u16) << 8  |
-           (self.get_byte()? as u16))
+    pub(crate) fn get_byte(&mut self) -> Result<u8, String> {
+        self.source.get_byte()
+    }
+
+    pub(crate) fn get_string(&mut self, len: usize) -> Result<String, String> {
+        self.source.get_string(len)
     }
 
-    fn get_dword(&mut self) -> Result<u32, String> {
-        Ok((self.get_byte()? as u32) << 24 |
-           (self.get_byte()? as u32) << 16 |
-           (self.get_byte()? as u32) << 8  |
-           (self.get_byte()? as u32))
+    pub(crate) fn get_word(&mut self) -> Result<u16, String> {
+        self.source.get_word()
+    }
+
+    pub(crate) fn get_dword(&mut self) -> Result<u32, String> {
+        self.source.get_dword()
+    }
+
+    pub(crate) fn get_vlq(&mut self) -> Result<u32, String> {
+        self.source.get_vlq()
     }
 }
 
@@ -134,11 +178,38 @@ pub fn cli_parse() -> Options {
         .arg(arg!(
             -d --debug ... "Turn debugging information on"
         ))
+        .arg(arg!(
+            --render <outfile> "Render the file to a WAV file using a SoundFont instead of viewing it"
+        ).required(false))
+        .arg(arg!(
+            --soundfont <sf2file> "SoundFont (.sf2) file to use with --render"
+        ).required(false))
+        .arg(arg!(
+            --from <format> "Input file format: \"mid\" or \"it\" (default: \"it\" for a .it file, else \"mid\")"
+        ).required(false))
         .get_matches();
 
-    let opts = Options{infile: matches.value_of("infile").unwrap().to_string(), debug: matches.is_present("debug")};
+    Options{
+        infile: matches.value_of("infile").unwrap().to_string(),
+        debug: matches.is_present("debug"),
+        render: matches.value_of("render").map(|s| s.to_string()),
+        soundfont: matches.value_of("soundfont").map(|s| s.to_string()),
+        from: matches.value_of("from").map(|s| s.to_string()),
+    }
+}
 
-    opts
+/// Resolves `--from` (falling back to sniffing the file extension) into
+/// an `InputFormat`, or an error message if an unknown format was given.
+/// Kept separate from argument parsing so the caller can log the error
+/// once a logger is installed.
+fn resolve_input_format(from: &Option<String>, infile: &str) -> Result<InputFormat, String> {
+    match from.as_deref() {
+        Some("mid") => Ok(InputFormat::Midi),
+        Some("it") => Ok(InputFormat::ImpulseTracker),
+        Some(format) => Err(format!("Unknown input format: \"{}\"", format)),
+        None if infile.to_lowercase().ends_with(".it") => Ok(InputFormat::ImpulseTracker),
+        None => Ok(InputFormat::Midi),
+    }
 }
 
 pub fn read_file(name: String) -> Result<Vec<u8>, std::io::Error>{
@@ -163,22 +234,47 @@ fn main() {
         .format_module_path(false)
         .init();
 
-    
-    let buffer: Vec<u8>;
-    match read_file(opts.infile) {
-        Ok(buff) => buffer = buff,
-        Err(err) => {
-            error!("{}", err.to_string());
+    let input_format = match resolve_input_format(&opts.from, &opts.infile) {
+        Ok(format) => format,
+        Err(str) => {
+            error!("{}", str);
             std::process::exit(1);
         },
     };
 
-    let _mid1;
-    match MIDIFile::new(buffer) {
-        Ok(mid) => _mid1 = mid,
+    let mid1;
+    let parsed = match input_format {
+        InputFormat::Midi => MIDIFile::open(&opts.infile),
+        InputFormat::ImpulseTracker => read_file(opts.infile).map_err(|err| err.to_string()).and_then(itmodule::import),
+    };
+    match parsed {
+        Ok(mid) => mid1 = mid,
         Err(str) => {
             error!("{}", str);
             std::process::exit(1);
         },
     }
+
+    if let Some(out_path) = opts.render {
+        let soundfont_path = match opts.soundfont {
+            Some(path) => path,
+            None => {
+                error!("--render requires --soundfont to be given");
+                std::process::exit(1);
+            },
+        };
+
+        let soundfont = match read_file(soundfont_path).map_err(|err| err.to_string()).and_then(soundfont::SoundFont::load) {
+            Ok(sf) => sf,
+            Err(str) => {
+                error!("{}", str);
+                std::process::exit(1);
+            },
+        };
+
+        if let Err(str) = render::render(&mid1, &soundfont, &out_path) {
+            error!("{}", str);
+            std::process::exit(1);
+        }
+    }
 }
\ No newline at end of file
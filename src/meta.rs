@@ -0,0 +1,159 @@
+/// A decoded `0xFF` meta event.
+///
+/// Text-based variants hold their bytes interpreted as Latin-1/UTF-8, as
+/// the MIDI spec does not mandate an encoding. Unrecognised meta types are
+/// kept around as `Unknown` so a viewer can still show something useful.
+pub enum MetaEvent {
+    Text(String),
+    Copyright(String),
+    TrackName(String),
+    InstrumentName(String),
+    Lyric(String),
+    Marker(String),
+    CuePoint(String),
+    EndOfTrack,
+    SetTempo {
+        microseconds_per_quarter_note: u32,
+    },
+    TimeSignature {
+        numerator: u8,
+        denominator: u8,
+        midi_clocks_per_click: u8,
+        notated_32nds_per_quarter: u8,
+    },
+    KeySignature {
+        sharps_flats: i8,
+        minor: bool,
+    },
+    Unknown {
+        meta_type: u8,
+        data: Vec<u8>,
+    },
+}
+
+impl MetaEvent {
+    /// Splits the event back into its type byte and payload, the inverse
+    /// of `decode_meta_event`.
+    pub(crate) fn encode(&self) -> (u8, Vec<u8>) {
+        match self {
+            MetaEvent::Text(text) => (0x01, text.chars().map(|c| c as u8).collect()),
+            MetaEvent::Copyright(text) => (0x02, text.chars().map(|c| c as u8).collect()),
+            MetaEvent::TrackName(text) => (0x03, text.chars().map(|c| c as u8).collect()),
+            MetaEvent::InstrumentName(text) => (0x04, text.chars().map(|c| c as u8).collect()),
+            MetaEvent::Lyric(text) => (0x05, text.chars().map(|c| c as u8).collect()),
+            MetaEvent::Marker(text) => (0x06, text.chars().map(|c| c as u8).collect()),
+            MetaEvent::CuePoint(text) => (0x07, text.chars().map(|c| c as u8).collect()),
+            MetaEvent::EndOfTrack => (0x2F, Vec::new()),
+            MetaEvent::SetTempo { microseconds_per_quarter_note } => {
+                let value = *microseconds_per_quarter_note;
+                (0x51, vec![(value >> 16) as u8, (value >> 8) as u8, value as u8])
+            },
+            MetaEvent::TimeSignature { numerator, denominator, midi_clocks_per_click, notated_32nds_per_quarter } =>
+                (0x58, vec![*numerator, *denominator, *midi_clocks_per_click, *notated_32nds_per_quarter]),
+            MetaEvent::KeySignature { sharps_flats, minor } =>
+                (0x59, vec![*sharps_flats as u8, *minor as u8]),
+            MetaEvent::Unknown { meta_type, data } => (*meta_type, data.clone()),
+        }
+    }
+}
+
+fn to_string(data: &[u8]) -> String {
+    data.iter().map(|&byte| byte as char).collect()
+}
+
+pub fn decode_meta_event(meta_type: u8, data: Vec<u8>) -> MetaEvent {
+    match meta_type {
+        0x01 => MetaEvent::Text(to_string(&data)),
+        0x02 => MetaEvent::Copyright(to_string(&data)),
+        0x03 => MetaEvent::TrackName(to_string(&data)),
+        0x04 => MetaEvent::InstrumentName(to_string(&data)),
+        0x05 => MetaEvent::Lyric(to_string(&data)),
+        0x06 => MetaEvent::Marker(to_string(&data)),
+        0x07 => MetaEvent::CuePoint(to_string(&data)),
+        0x2F => MetaEvent::EndOfTrack,
+        0x51 if data.len() == 3 => MetaEvent::SetTempo {
+            microseconds_per_quarter_note: (data[0] as u32) << 16 | (data[1] as u32) << 8 | (data[2] as u32),
+        },
+        0x58 if data.len() == 4 => MetaEvent::TimeSignature {
+            numerator: data[0],
+            denominator: data[1],
+            midi_clocks_per_click: data[2],
+            notated_32nds_per_quarter: data[3],
+        },
+        0x59 if data.len() == 2 => MetaEvent::KeySignature {
+            sharps_flats: data[0] as i8,
+            minor: data[1] == 1,
+        },
+        meta_type @ _ => MetaEvent::Unknown { meta_type, data },
+    }
+}
+
+/// Render a meta event the way the viewer prints its timeline.
+pub fn describe(event: &MetaEvent) -> String {
+    match event {
+        MetaEvent::Text(text) => format!("Text: {}", text),
+        MetaEvent::Copyright(text) => format!("Copyright: {}", text),
+        MetaEvent::TrackName(text) => format!("Track Name: {}", text),
+        MetaEvent::InstrumentName(text) => format!("Instrument Name: {}", text),
+        MetaEvent::Lyric(text) => format!("Lyric: {}", text),
+        MetaEvent::Marker(text) => format!("Marker: {}", text),
+        MetaEvent::CuePoint(text) => format!("Cue Point: {}", text),
+        MetaEvent::EndOfTrack => "End of Track".to_string(),
+        MetaEvent::SetTempo { microseconds_per_quarter_note } =>
+            format!("Set Tempo: {} µs/quarter note", microseconds_per_quarter_note),
+        MetaEvent::TimeSignature { numerator, denominator, midi_clocks_per_click, notated_32nds_per_quarter } =>
+            format!("Time Signature: {}/{}, {} MIDI clocks/click, {} 32nds/quarter",
+                numerator, 1u32 << denominator, midi_clocks_per_click, notated_32nds_per_quarter),
+        MetaEvent::KeySignature { sharps_flats, minor } =>
+            format!("Key Signature: {} {}", sharps_flats, if *minor { "minor" } else { "major" }),
+        MetaEvent::Unknown { meta_type, data } =>
+            format!("Unknown Meta Event {:#04x}, {} bytes", meta_type, data.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_tempo_round_trips() {
+        let (meta_type, data) = (MetaEvent::SetTempo { microseconds_per_quarter_note: 500_000 }).encode();
+        match decode_meta_event(meta_type, data) {
+            MetaEvent::SetTempo { microseconds_per_quarter_note } => assert_eq!(microseconds_per_quarter_note, 500_000),
+            _ => panic!("expected SetTempo"),
+        }
+    }
+
+    #[test]
+    fn track_name_round_trips() {
+        let (meta_type, data) = MetaEvent::TrackName("Lead".to_string()).encode();
+        match decode_meta_event(meta_type, data) {
+            MetaEvent::TrackName(name) => assert_eq!(name, "Lead"),
+            _ => panic!("expected TrackName"),
+        }
+    }
+
+    #[test]
+    fn key_signature_round_trips_negative_sharps_flats() {
+        let (meta_type, data) = (MetaEvent::KeySignature { sharps_flats: -3, minor: true }).encode();
+        match decode_meta_event(meta_type, data) {
+            MetaEvent::KeySignature { sharps_flats, minor } => {
+                assert_eq!(sharps_flats, -3);
+                assert!(minor);
+            },
+            _ => panic!("expected KeySignature"),
+        }
+    }
+
+    #[test]
+    fn unknown_meta_type_is_preserved() {
+        let (meta_type, data) = MetaEvent::Unknown { meta_type: 0x7F, data: vec![1, 2, 3] }.encode();
+        match decode_meta_event(meta_type, data) {
+            MetaEvent::Unknown { meta_type, data } => {
+                assert_eq!(meta_type, 0x7F);
+                assert_eq!(data, vec![1, 2, 3]);
+            },
+            _ => panic!("expected Unknown"),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::io::Write;
+
+use log::debug;
+
+use crate::{Event, MIDIFile};
+use crate::soundfont::SoundFont;
+
+const OUTPUT_SAMPLE_RATE: u32 = 44_100;
+const RELEASE_SECONDS: f64 = 0.2;
+
+struct Voice {
+    sample_index: usize,
+    position: f64,
+    increment: f64,
+    amplitude: f64,
+    start_time: f64,
+    releasing_at: Option<f64>,
+}
+
+/// Synthesizes the file's events with `soundfont` and writes the result
+/// as a mono WAV file to `out_path`.
+pub fn render(file: &MIDIFile, soundfont: &SoundFont, out_path: &str) -> Result<(), String> {
+    let schedule = schedule_channel_voice_events(file);
+
+    let mut channel_programs = [0u8; 16];
+    let mut voices: Vec<(u8, u8, Voice)> = Vec::new();
+
+    for (time, status, channel, data1, data2) in &schedule {
+        match *status {
+            0xC0 => channel_programs[*channel as usize] = *data1,
+            0x90 if data2.unwrap_or(0) > 0 => {
+                match soundfont.find_zone(channel_programs[*channel as usize], *data1) {
+                    Some(zone) => {
+                        let sample = match soundfont.samples.get(zone.sample_index) {
+                            Some(sample) => sample,
+                            None => {
+                                debug!("SoundFont sample index {} out of range ({} samples)", zone.sample_index, soundfont.samples.len());
+                                continue;
+                            },
+                        };
+                        let played_frequency = note_frequency(*data1);
+                        let original_frequency = note_frequency(sample.original_pitch)
+                            * 2f64.powf(sample.pitch_correction as f64 / 1200.0);
+                        let increment = (played_frequency / original_frequency)
+                            * sample.sample_rate as f64 / OUTPUT_SAMPLE_RATE as f64;
+                        voices.push((*channel, *data1, Voice {
+                            sample_index: zone.sample_index,
+                            position: 0.0,
+                            increment,
+                            amplitude: data2.unwrap_or(0) as f64 / 127.0,
+                            start_time: *time,
+                            releasing_at: None,
+                        }));
+                    },
+                    None => debug!("No instrument zone for program {} note {}", channel_programs[*channel as usize], data1),
+                }
+            },
+            0x80 | 0x90 => {
+                for (voice_channel, voice_note, voice) in voices.iter_mut() {
+                    if *voice_channel == *channel && *voice_note == *data1 && voice.releasing_at.is_none() {
+                        voice.releasing_at = Some(*time);
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    let total_seconds = schedule.last().map(|(time, ..)| *time).unwrap_or(0.0) + RELEASE_SECONDS + 1.0;
+    let mut mix = vec![0.0f64; (total_seconds * OUTPUT_SAMPLE_RATE as f64) as usize];
+
+    for (_channel, _note, voice) in voices {
+        mix_voice(&mut mix, soundfont, voice);
+    }
+
+    write_wav(out_path, &mix)
+}
+
+/// Renders one voice's samples, pitch-shifted by `increment`, into `mix`
+/// starting at its note-on time and fading out linearly after note-off.
+fn mix_voice(mix: &mut [f64], soundfont: &SoundFont, mut voice: Voice) {
+    let sample = match soundfont.samples.get(voice.sample_index) {
+        Some(sample) => sample,
+        None => {
+            debug!("SoundFont sample index {} out of range ({} samples)", voice.sample_index, soundfont.samples.len());
+            return;
+        },
+    };
+    let sample_len = (sample.end - sample.start) as usize;
+    let release_start_sample = voice.releasing_at.map(|time| (time * OUTPUT_SAMPLE_RATE as f64) as usize);
+    let mut output_index = (voice.start_time * OUTPUT_SAMPLE_RATE as f64) as usize;
+
+    while (voice.position as usize) < sample_len && output_index < mix.len() {
+        let mut envelope = voice.amplitude;
+        if let Some(release_start) = release_start_sample {
+            if output_index >= release_start {
+                let release_progress = (output_index - release_start) as f64 / (RELEASE_SECONDS * OUTPUT_SAMPLE_RATE as f64);
+                if release_progress >= 1.0 {
+                    break;
+                }
+                envelope *= 1.0 - release_progress;
+            }
+        }
+
+        let raw_sample = soundfont.sample_data[sample.start as usize + voice.position as usize] as f64 / i16::MAX as f64;
+        mix[output_index] += raw_sample * envelope;
+
+        voice.position += voice.increment;
+        output_index += 1;
+    }
+}
+
+/// Flattens every track's channel voice events into one chronologically
+/// sorted `(time, status, channel, data1, data2)` list.
+fn schedule_channel_voice_events(file: &MIDIFile) -> Vec<(f64, u8, u8, u8, Option<u8>)> {
+    let tempo_map = file.build_tempo_map();
+    let mut scheduled = Vec::new();
+    for track in &file.tracks {
+        let mut tick: u64 = 0;
+        for track_event in &track.events {
+            tick += track_event.delta_time as u64;
+            if let Event::ChannelVoice { status, channel, data1, data2 } = track_event.event {
+                scheduled.push((file.tick_to_seconds(tick, &tempo_map), status, channel, data1, data2));
+            }
+        }
+    }
+    scheduled.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    scheduled
+}
+
+fn note_frequency(note: u8) -> f64 {
+    440.0 * 2f64.powf((note as f64 - 69.0) / 12.0)
+}
+
+fn write_wav(path: &str, samples: &[f64]) -> Result<(), String> {
+    let mut file = std::fs::File::create(path).map_err(|err| err.to_string())?;
+
+    let channels: u16 = 1;
+    let bits_per_sample: u16 = 16;
+    let byte_rate = OUTPUT_SAMPLE_RATE * channels as u32 * (bits_per_sample / 8) as u32;
+    let block_align = channels * (bits_per_sample / 8);
+    let data_size = samples.len() as u32 * (bits_per_sample / 8) as u32;
+
+    file.write_all(b"RIFF").map_err(|err| err.to_string())?;
+    file.write_all(&(36 + data_size).to_le_bytes()).map_err(|err| err.to_string())?;
+    file.write_all(b"WAVE").map_err(|err| err.to_string())?;
+
+    file.write_all(b"fmt ").map_err(|err| err.to_string())?;
+    file.write_all(&16u32.to_le_bytes()).map_err(|err| err.to_string())?;
+    file.write_all(&1u16.to_le_bytes()).map_err(|err| err.to_string())?;
+    file.write_all(&channels.to_le_bytes()).map_err(|err| err.to_string())?;
+    file.write_all(&OUTPUT_SAMPLE_RATE.to_le_bytes()).map_err(|err| err.to_string())?;
+    file.write_all(&byte_rate.to_le_bytes()).map_err(|err| err.to_string())?;
+    file.write_all(&block_align.to_le_bytes()).map_err(|err| err.to_string())?;
+    file.write_all(&bits_per_sample.to_le_bytes()).map_err(|err| err.to_string())?;
+
+    file.write_all(b"data").map_err(|err| err.to_string())?;
+    file.write_all(&data_size.to_le_bytes()).map_err(|err| err.to_string())?;
+    for &sample in samples {
+        let clamped = sample.clamp(-1.0, 1.0);
+        let value = (clamped * i16::MAX as f64) as i16;
+        file.write_all(&value.to_le_bytes()).map_err(|err| err.to_string())?;
+    }
+
+    Ok(())
+}
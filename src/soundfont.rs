@@ -0,0 +1,278 @@
+/// A minimal SoundFont 2 (`.sf2`) reader: just enough of the RIFF
+/// structure to map a MIDI program number and key to the 16-bit PCM
+/// sample that should sound for it. Generators other than `instrument`,
+/// `sampleID` and `keyRange` are ignored, as are modulators and global
+/// zones.
+pub struct SoundFont {
+    pub samples: Vec<SampleHeader>,
+    pub sample_data: Vec<i16>,
+    pub presets: Vec<Preset>,
+}
+
+pub struct SampleHeader {
+    pub start: u32,
+    pub end: u32,
+    pub sample_rate: u32,
+    pub original_pitch: u8,
+    pub pitch_correction: i8,
+}
+
+pub struct Preset {
+    pub program: u8,
+    pub bank: u16,
+    pub zones: Vec<PresetZone>,
+}
+
+pub struct PresetZone {
+    pub key_range: (u8, u8),
+    pub sample_index: usize,
+}
+
+struct Instrument {
+    zones: Vec<PresetZone>,
+}
+
+/// Little-endian byte cursor over an in-memory `.sf2` file, mirroring
+/// `MIDIFile`'s big-endian one.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data, pos: 0 }
+    }
+
+    fn get_bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.pos + len > self.data.len() {
+            return Err("File ended unexpectedly".to_string());
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn get_u8(&mut self) -> Result<u8, String> {
+        Ok(self.get_bytes(1)?[0])
+    }
+
+    fn get_i8(&mut self) -> Result<i8, String> {
+        Ok(self.get_u8()? as i8)
+    }
+
+    fn get_u16(&mut self) -> Result<u16, String> {
+        let bytes = self.get_bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn get_u32(&mut self) -> Result<u32, String> {
+        let bytes = self.get_bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn get_string(&mut self, len: usize) -> Result<String, String> {
+        let bytes = self.get_bytes(len)?;
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(len);
+        Ok(bytes[..end].iter().map(|&b| b as char).collect())
+    }
+}
+
+/// One `(oper, amount)` generator entry from a `pgen`/`igen` sub-chunk.
+struct Generator {
+    oper: u16,
+    amount: u16,
+}
+
+impl SoundFont {
+    pub fn load(buffer: Vec<u8>) -> Result<SoundFont, String> {
+        let mut r = Reader::new(&buffer);
+
+        let identifier = r.get_string(4)?;
+        if identifier != "RIFF" {
+            return Err(format!("Wrong identifier for RIFF chunk: Expected \"RIFF\" but got \"{}\"", identifier));
+        }
+        let _riff_size = r.get_u32()?;
+        let form_type = r.get_string(4)?;
+        if form_type != "sfbk" {
+            return Err(format!("Wrong RIFF form type: Expected \"sfbk\" but got \"{}\"", form_type));
+        }
+
+        let mut sample_data = Vec::new();
+        let mut phdr = Vec::new();
+        let mut pbag = Vec::new();
+        let mut pgen = Vec::new();
+        let mut inst = Vec::new();
+        let mut ibag = Vec::new();
+        let mut igen = Vec::new();
+        let mut shdr = Vec::new();
+
+        while r.pos < buffer.len() {
+            let list_id = r.get_string(4)?;
+            if list_id != "LIST" {
+                return Err(format!("Wrong identifier for LIST chunk: Expected \"LIST\" but got \"{}\"", list_id));
+            }
+            let list_size = r.get_u32()? as usize;
+            let list_end = r.pos + list_size;
+            let list_type = r.get_string(4)?;
+
+            match list_type.as_str() {
+                "sdta" => {
+                    while r.pos < list_end {
+                        let chunk_id = r.get_string(4)?;
+                        let chunk_size = r.get_u32()? as usize;
+                        if chunk_id == "smpl" {
+                            let bytes = r.get_bytes(chunk_size)?;
+                            sample_data = bytes.chunks_exact(2)
+                                .map(|b| i16::from_le_bytes([b[0], b[1]]))
+                                .collect();
+                        } else {
+                            r.get_bytes(chunk_size)?;
+                        }
+                    }
+                },
+                "pdta" => {
+                    while r.pos < list_end {
+                        let chunk_id = r.get_string(4)?;
+                        let chunk_size = r.get_u32()? as usize;
+                        let chunk_end = r.pos + chunk_size;
+                        match chunk_id.as_str() {
+                            "phdr" => while r.pos < chunk_end { phdr.push(read_phdr(&mut r)?); },
+                            "pbag" => while r.pos < chunk_end { pbag.push(read_bag(&mut r)?); },
+                            "pgen" => while r.pos < chunk_end { pgen.push(read_gen(&mut r)?); },
+                            "inst" => while r.pos < chunk_end { inst.push(read_inst(&mut r)?); },
+                            "ibag" => while r.pos < chunk_end { ibag.push(read_bag(&mut r)?); },
+                            "igen" => while r.pos < chunk_end { igen.push(read_gen(&mut r)?); },
+                            "shdr" => while r.pos < chunk_end { shdr.push(read_shdr(&mut r)?); },
+                            _ => { r.get_bytes(chunk_size)?; },
+                        }
+                    }
+                },
+                _ => {
+                    r.pos = list_end;
+                },
+            }
+        }
+
+        let instruments: Vec<Instrument> = (0..inst.len().saturating_sub(1)).map(|i| {
+            let (_name, bag_start) = &inst[i];
+            let (_next_name, bag_end) = &inst[i + 1];
+            Instrument { zones: resolve_zones(&ibag, &igen, *bag_start as usize, *bag_end as usize, |gens| {
+                gens.iter().find(|g| g.oper == 53).map(|g| g.amount as usize)
+            }) }
+        }).collect();
+
+        let presets: Vec<Preset> = (0..phdr.len().saturating_sub(1)).map(|i| {
+            let header = &phdr[i];
+            let next = &phdr[i + 1];
+            let zones = resolve_zones(&pbag, &pgen, header.bag_index as usize, next.bag_index as usize, |gens| {
+                gens.iter().find(|g| g.oper == 41).map(|g| g.amount as usize)
+            });
+            let zones = zones.into_iter()
+                .filter_map(|zone| instruments.get(zone.sample_index))
+                .flat_map(|instrument| instrument.zones.iter().map(|z| PresetZone { key_range: z.key_range, sample_index: z.sample_index }))
+                .collect();
+            Preset { program: header.preset as u8, bank: header.bank, zones }
+        }).collect();
+
+        let samples = shdr.into_iter().map(|s| SampleHeader {
+            start: s.start,
+            end: s.end,
+            sample_rate: s.sample_rate,
+            original_pitch: s.original_pitch,
+            pitch_correction: s.pitch_correction,
+        }).collect();
+
+        Ok(SoundFont { samples, sample_data, presets })
+    }
+
+    pub fn find_zone(&self, program: u8, note: u8) -> Option<&PresetZone> {
+        self.presets.iter()
+            .find(|preset| preset.program == program)
+            .and_then(|preset| preset.zones.iter().find(|zone| note >= zone.key_range.0 && note <= zone.key_range.1))
+    }
+}
+
+struct PresetHeader {
+    preset: u16,
+    bank: u16,
+    bag_index: u16,
+}
+
+struct SampleRecord {
+    start: u32,
+    end: u32,
+    sample_rate: u32,
+    original_pitch: u8,
+    pitch_correction: i8,
+}
+
+fn read_phdr(r: &mut Reader) -> Result<PresetHeader, String> {
+    let _name = r.get_string(20)?;
+    let preset = r.get_u16()?;
+    let bank = r.get_u16()?;
+    let bag_index = r.get_u16()?;
+    let _library = r.get_u32()?;
+    let _genre = r.get_u32()?;
+    let _morphology = r.get_u32()?;
+    Ok(PresetHeader { preset, bank, bag_index })
+}
+
+fn read_inst(r: &mut Reader) -> Result<(String, u16), String> {
+    let name = r.get_string(20)?;
+    let bag_index = r.get_u16()?;
+    Ok((name, bag_index))
+}
+
+fn read_bag(r: &mut Reader) -> Result<(u16, u16), String> {
+    let gen_index = r.get_u16()?;
+    let mod_index = r.get_u16()?;
+    Ok((gen_index, mod_index))
+}
+
+fn read_gen(r: &mut Reader) -> Result<Generator, String> {
+    let oper = r.get_u16()?;
+    let amount = r.get_u16()?;
+    Ok(Generator { oper, amount })
+}
+
+fn read_shdr(r: &mut Reader) -> Result<SampleRecord, String> {
+    let _name = r.get_string(20)?;
+    let start = r.get_u32()?;
+    let end = r.get_u32()?;
+    let _loop_start = r.get_u32()?;
+    let _loop_end = r.get_u32()?;
+    let sample_rate = r.get_u32()?;
+    let original_pitch = r.get_u8()?;
+    let pitch_correction = r.get_i8()?;
+    let _sample_link = r.get_u16()?;
+    let _sample_type = r.get_u16()?;
+    Ok(SampleRecord { start, end, sample_rate, original_pitch, pitch_correction })
+}
+
+/// Walks the zones of a `pbag`/`ibag` range, pulling out the key range
+/// (generator 43) and whatever the caller's index generator resolves to.
+fn resolve_zones(
+    bags: &[(u16, u16)],
+    gens: &[Generator],
+    bag_start: usize,
+    bag_end: usize,
+    index_gen: impl Fn(&[Generator]) -> Option<usize>,
+) -> Vec<PresetZone> {
+    let mut zones = Vec::new();
+    for i in bag_start..bag_end.min(bags.len().saturating_sub(1)) {
+        let gen_start = bags[i].0 as usize;
+        let gen_end = bags[i + 1].0 as usize;
+        if gen_start > gen_end || gen_end > gens.len() {
+            continue;
+        }
+        let zone_gens = &gens[gen_start..gen_end];
+        let key_range = zone_gens.iter().find(|g| g.oper == 43)
+            .map(|g| ((g.amount & 0xFF) as u8, (g.amount >> 8) as u8))
+            .unwrap_or((0, 127));
+        if let Some(index) = index_gen(zone_gens) {
+            zones.push(PresetZone { key_range, sample_index: index });
+        }
+    }
+    zones
+}
@@ -0,0 +1,154 @@
+use crate::{MIDIFile, MetaEvent, Event};
+
+/// The `division` field of the header chunk, fully decoded.
+#[derive(Clone, Copy)]
+pub enum Division {
+    TicksPerBeat(u16),
+    Smpte {
+        frames_per_second: i8,
+        ticks_per_frame: u8,
+    },
+}
+
+impl Division {
+    pub(crate) fn decode(raw: i16) -> Result<Division, String> {
+        if raw > 0 {
+            Ok(Division::TicksPerBeat(raw as u16))
+        } else if raw < 0 {
+            let frames_per_second = (raw >> 8) as i8;
+            let ticks_per_frame = (raw & 0xFF) as u8;
+            match frames_per_second {
+                -24 | -25 | -29 | -30 => Ok(Division::Smpte { frames_per_second, ticks_per_frame }),
+                rate @ _ => Err(format!("Invalid SMPTE frame rate: {}", rate).to_string()),
+            }
+        } else {
+            Err("Division cannot be zero".to_string())
+        }
+    }
+
+    pub(crate) fn encode(&self) -> u16 {
+        match self {
+            Division::TicksPerBeat(ticks) => *ticks,
+            Division::Smpte { frames_per_second, ticks_per_frame } =>
+                ((*frames_per_second as i16) << 8 | *ticks_per_frame as i16) as u16,
+        }
+    }
+
+    /// Ticks per second, only meaningful in SMPTE mode.
+    fn resolution(&self) -> f64 {
+        match self {
+            Division::TicksPerBeat(_) => unreachable!("resolution is only defined in SMPTE mode"),
+            Division::Smpte { frames_per_second, ticks_per_frame } => {
+                let frames_per_second = match frames_per_second {
+                    -29 => 29.97,
+                    rate => -(*rate as f64),
+                };
+                frames_per_second * (*ticks_per_frame as f64)
+            },
+        }
+    }
+}
+
+/// A point at which the tempo changes, in absolute ticks since the start
+/// of the file.
+pub(crate) struct TempoChange {
+    tick: u64,
+    microseconds_per_quarter_note: u32,
+}
+
+const DEFAULT_MICROSECONDS_PER_QUARTER_NOTE: u32 = 500_000;
+
+impl MIDIFile {
+
+    /// Converts every event's cumulative delta-time into an absolute time
+    /// in seconds, using the header's division and, in ticks-per-beat
+    /// mode, the tempo map formed by the file's `SetTempo` meta events.
+    pub fn event_times(&self) -> Vec<f64> {
+        let tempo_map = match self.division {
+            Division::TicksPerBeat(_) => self.build_tempo_map(),
+            Division::Smpte { .. } => Vec::new(),
+        };
+
+        let mut times = Vec::new();
+        for track in &self.tracks {
+            let mut tick: u64 = 0;
+            for track_event in &track.events {
+                tick += track_event.delta_time as u64;
+                times.push(self.tick_to_seconds(tick, &tempo_map));
+            }
+        }
+        times
+    }
+
+    pub(crate) fn build_tempo_map(&self) -> Vec<TempoChange> {
+        let mut tempo_map = Vec::new();
+        for track in &self.tracks {
+            let mut tick: u64 = 0;
+            for track_event in &track.events {
+                tick += track_event.delta_time as u64;
+                if let Event::Meta(MetaEvent::SetTempo { microseconds_per_quarter_note }) = &track_event.event {
+                    tempo_map.push(TempoChange { tick, microseconds_per_quarter_note: *microseconds_per_quarter_note });
+                }
+            }
+        }
+        tempo_map.sort_by_key(|change| change.tick);
+        tempo_map
+    }
+
+    pub(crate) fn tick_to_seconds(&self, tick: u64, tempo_map: &[TempoChange]) -> f64 {
+        match self.division {
+            Division::Smpte { .. } => tick as f64 / self.division.resolution(),
+            Division::TicksPerBeat(ticks_per_beat) => {
+                let mut seconds = 0.0;
+                let mut previous_tick: u64 = 0;
+                let mut microseconds_per_quarter_note = DEFAULT_MICROSECONDS_PER_QUARTER_NOTE;
+                for change in tempo_map {
+                    if change.tick >= tick {
+                        break;
+                    }
+                    seconds += seconds_per_tick(microseconds_per_quarter_note, ticks_per_beat) * (change.tick - previous_tick) as f64;
+                    previous_tick = change.tick;
+                    microseconds_per_quarter_note = change.microseconds_per_quarter_note;
+                }
+                seconds += seconds_per_tick(microseconds_per_quarter_note, ticks_per_beat) * (tick - previous_tick) as f64;
+                seconds
+            },
+        }
+    }
+}
+
+fn seconds_per_tick(microseconds_per_quarter_note: u32, ticks_per_beat: u16) -> f64 {
+    (microseconds_per_quarter_note as f64 / 1_000_000.0) / ticks_per_beat as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_per_beat_round_trips() {
+        let division = Division::decode(480).unwrap();
+        assert_eq!(division.encode(), 480);
+    }
+
+    #[test]
+    fn smpte_round_trips_for_every_valid_frame_rate() {
+        for frames_per_second in [-24i16, -25, -29, -30] {
+            let raw = frames_per_second * 256 + 40;
+            let division = Division::decode(raw).unwrap();
+            assert_eq!(division.encode() as i16, raw);
+        }
+    }
+
+    #[test]
+    fn rejects_zero_and_unknown_frame_rates() {
+        assert!(Division::decode(0).is_err());
+        assert!(Division::decode(-23i16 * 256).is_err());
+    }
+
+    #[test]
+    fn smpte_29_resolution_uses_drop_frame_rate() {
+        let division = Division::Smpte { frames_per_second: -29, ticks_per_frame: 80 };
+        assert_eq!(division.resolution(), 29.97 * 80.0);
+    }
+}
@@ -0,0 +1,120 @@
+use log::debug;
+
+use crate::MIDIFile;
+use crate::meta::{self, MetaEvent};
+
+/// A single `MTrk` chunk: an ordered list of delta-timed events.
+pub struct Track {
+    pub events: Vec<TrackEvent>,
+}
+
+/// An event together with the number of ticks since the previous event
+/// in the same track.
+pub struct TrackEvent {
+    pub delta_time: u32,
+    pub event: Event,
+}
+
+pub enum Event {
+    ChannelVoice {
+        status: u8,
+        channel: u8,
+        data1: u8,
+        data2: Option<u8>,
+    },
+    SysEx {
+        kind: u8,
+        data: Vec<u8>,
+    },
+    Meta(MetaEvent),
+}
+
+impl MIDIFile {
+
+    pub(crate) fn read_tracks(&mut self) -> Result<(), String> {
+        for _i in 0..self.number_of_track_chunks {
+            let track = self.read_track()?;
+            self.tracks.push(track);
+        }
+        Ok(())
+    }
+
+    fn read_track(&mut self) -> Result<Track, String> {
+        let identifier = self.get_string(4)?;
+        if identifier != "MTrk" {
+            return Err(
+                format!("Wrong identifier for track chunk: Expected \"MTrk\" but got \"{}\"", identifier)
+                .to_string());
+        }
+
+        let _chunk_length = self.get_dword()?;
+
+        let mut events = Vec::new();
+        let mut running_status: Option<u8> = None;
+
+        loop {
+            let delta_time = self.get_vlq()?;
+            let byte = self.get_byte()?;
+
+            let (status, first_data_byte) = if byte < 0x80 {
+                let status = running_status.ok_or("Running status byte encountered with no previous status".to_string())?;
+                (status, Some(byte))
+            } else {
+                (byte, None)
+            };
+
+            let event = match status {
+                0xFF => {
+                    running_status = None;
+                    let meta_type = self.get_byte()?;
+                    let length = self.get_vlq()?;
+                    let mut data = Vec::with_capacity(length as usize);
+                    for _i in 0..length {
+                        data.push(self.get_byte()?);
+                    }
+                    let meta_event = meta::decode_meta_event(meta_type, data);
+                    debug!("{}", meta::describe(&meta_event));
+                    let is_end_of_track = matches!(meta_event, MetaEvent::EndOfTrack);
+                    events.push(TrackEvent { delta_time, event: Event::Meta(meta_event) });
+                    if is_end_of_track {
+                        break;
+                    }
+                    continue;
+                },
+                0xF0 | 0xF7 => {
+                    running_status = None;
+                    let length = self.get_vlq()?;
+                    let mut data = Vec::with_capacity(length as usize);
+                    for _i in 0..length {
+                        data.push(self.get_byte()?);
+                    }
+                    debug!("SysEx event {:#04x}, {} bytes", status, data.len());
+                    Event::SysEx { kind: status, data }
+                },
+                0x80..=0xEF => {
+                    running_status = Some(status);
+                    let channel = status & 0x0F;
+                    let command = status & 0xF0;
+                    let data1 = match first_data_byte {
+                        Some(byte) => byte,
+                        None => self.get_byte()?,
+                    };
+                    let data2 = if command == 0xC0 || command == 0xD0 {
+                        None
+                    } else {
+                        Some(self.get_byte()?)
+                    };
+                    debug!("Channel voice event {:#04x} on channel {}", command, channel);
+                    Event::ChannelVoice { status: command, channel, data1, data2 }
+                },
+                status @ _ => {
+                    return Err(format!("Unknown status byte: {:#04x}", status).to_string());
+                },
+            };
+
+            events.push(TrackEvent { delta_time, event });
+        }
+
+        Ok(Track { events })
+    }
+}
@@ -0,0 +1,168 @@
+use std::io::Write;
+
+use crate::{Division, Event, FileFormat, MIDIFile, Track};
+
+/// Configures how `MIDIFile::write` serializes a file.
+pub struct Settings {
+    format: FileFormat,
+    division: Division,
+    running_status: bool,
+}
+
+impl Settings {
+    pub fn new(format: FileFormat, division: Division) -> Settings {
+        Settings { format, division, running_status: false }
+    }
+
+    /// Omit the status byte of a channel voice event whose status equals
+    /// the previously written one.
+    pub fn running_status(mut self, enabled: bool) -> Settings {
+        self.running_status = enabled;
+        self
+    }
+}
+
+impl MIDIFile {
+    pub fn write<W: Write>(&self, w: &mut W, settings: Settings) -> Result<(), String> {
+        put_string(w, "MThd")?;
+        put_dword(w, 6)?;
+        put_word(w, settings.format.as_word())?;
+        put_word(w, self.tracks.len() as u16)?;
+        put_word(w, settings.division.encode())?;
+
+        for track in &self.tracks {
+            let mut chunk = Vec::new();
+            write_track(&mut chunk, track, settings.running_status)?;
+            put_string(w, "MTrk")?;
+            put_dword(w, chunk.len() as u32)?;
+            w.write_all(&chunk).map_err(|err| err.to_string())?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_track<W: Write>(w: &mut W, track: &Track, running_status: bool) -> Result<(), String> {
+    let mut last_status: Option<u8> = None;
+
+    for track_event in &track.events {
+        put_vlq(w, track_event.delta_time)?;
+
+        match &track_event.event {
+            Event::ChannelVoice { status, channel, data1, data2 } => {
+                let status_byte = status | channel;
+                if !(running_status && last_status == Some(status_byte)) {
+                    put_byte(w, status_byte)?;
+                }
+                last_status = Some(status_byte);
+                put_byte(w, *data1)?;
+                if let Some(data2) = data2 {
+                    put_byte(w, *data2)?;
+                }
+            },
+            Event::SysEx { kind, data } => {
+                last_status = None;
+                put_byte(w, *kind)?;
+                put_vlq(w, data.len() as u32)?;
+                w.write_all(data).map_err(|err| err.to_string())?;
+            },
+            Event::Meta(meta_event) => {
+                last_status = None;
+                let (meta_type, data) = meta_event.encode();
+                put_byte(w, 0xFF)?;
+                put_byte(w, meta_type)?;
+                put_vlq(w, data.len() as u32)?;
+                w.write_all(&data).map_err(|err| err.to_string())?;
+            },
+        }
+    }
+
+    Ok(())
+}
+
+fn put_byte<W: Write>(w: &mut W, byte: u8) -> Result<(), String> {
+    w.write_all(&[byte]).map_err(|err| err.to_string())
+}
+
+fn put_word<W: Write>(w: &mut W, word: u16) -> Result<(), String> {
+    w.write_all(&word.to_be_bytes()).map_err(|err| err.to_string())
+}
+
+fn put_dword<W: Write>(w: &mut W, dword: u32) -> Result<(), String> {
+    w.write_all(&dword.to_be_bytes()).map_err(|err| err.to_string())
+}
+
+fn put_vlq<W: Write>(w: &mut W, value: u32) -> Result<(), String> {
+    let mut bytes = vec![(value & 0x7F) as u8];
+    let mut value = value >> 7;
+    while value > 0 {
+        bytes.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.reverse();
+    w.write_all(&bytes).map_err(|err| err.to_string())
+}
+
+fn put_string<W: Write>(w: &mut W, s: &str) -> Result<(), String> {
+    w.write_all(s.as_bytes()).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::byte_source::{BufferSource, ByteSource};
+    use crate::TrackEvent;
+
+    #[test]
+    fn put_vlq_round_trips_across_length_boundaries() {
+        for &value in &[0x00000000, 0x0000007F, 0x00000080, 0x00001FFF, 0x00002000, 0x000FFFFF, 0x00100000, 0x0FFFFFFF, 0x10000000] {
+            let mut buffer = Vec::new();
+            put_vlq(&mut buffer, value).unwrap();
+            let mut source = BufferSource::new(buffer);
+            assert_eq!(source.get_vlq().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn running_status_omits_repeated_status_byte() {
+        let track = Track { events: vec![
+            TrackEvent { delta_time: 0, event: Event::ChannelVoice { status: 0x90, channel: 0, data1: 60, data2: Some(100) } },
+            TrackEvent { delta_time: 10, event: Event::ChannelVoice { status: 0x90, channel: 0, data1: 64, data2: Some(90) } },
+        ] };
+
+        let mut buffer = Vec::new();
+        write_track(&mut buffer, &track, true).unwrap();
+        let mut source = BufferSource::new(buffer);
+
+        assert_eq!(source.get_vlq().unwrap(), 0);
+        assert_eq!(source.get_byte().unwrap(), 0x90);
+        assert_eq!(source.get_byte().unwrap(), 60);
+        assert_eq!(source.get_byte().unwrap(), 100);
+
+        assert_eq!(source.get_vlq().unwrap(), 10);
+        assert_eq!(source.get_byte().unwrap(), 64);
+        assert_eq!(source.get_byte().unwrap(), 90);
+    }
+
+    #[test]
+    fn without_running_status_the_status_byte_is_repeated() {
+        let track = Track { events: vec![
+            TrackEvent { delta_time: 0, event: Event::ChannelVoice { status: 0x90, channel: 0, data1: 60, data2: Some(100) } },
+            TrackEvent { delta_time: 10, event: Event::ChannelVoice { status: 0x90, channel: 0, data1: 64, data2: Some(90) } },
+        ] };
+
+        let mut buffer = Vec::new();
+        write_track(&mut buffer, &track, false).unwrap();
+        let mut source = BufferSource::new(buffer);
+
+        assert_eq!(source.get_vlq().unwrap(), 0);
+        assert_eq!(source.get_byte().unwrap(), 0x90);
+        assert_eq!(source.get_byte().unwrap(), 60);
+        assert_eq!(source.get_byte().unwrap(), 100);
+
+        assert_eq!(source.get_vlq().unwrap(), 10);
+        assert_eq!(source.get_byte().unwrap(), 0x90);
+        assert_eq!(source.get_byte().unwrap(), 64);
+        assert_eq!(source.get_byte().unwrap(), 90);
+    }
+}